@@ -0,0 +1,465 @@
+//! msg-logger-cli：脱离 Bot 运行时，直接对 msg_history.sqlite 做离线查询与维护
+//!
+//! ```text
+//! msg-logger-cli --data-dir data/kovi-plugin-msg-logger word-cloud --group 123456789 --days 30
+//! msg-logger-cli --db backup.sqlite --json top-talkers --group 123456789
+//! ```
+
+use clap::{Args, Parser, Subcommand};
+use kovi::chrono::NaiveDate;
+use kovi_plugin_msg_logger::config::{Config, ConfigSnapshot};
+use kovi_plugin_msg_logger::db::{
+    ExportFormat, Logger, LoggerBuilder, LoggerError, MessageFilter, QueryApi,
+};
+use kovi_plugin_msg_logger::entities::messages;
+use serde::Serialize;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+#[derive(Parser)]
+#[command(
+    name = "msg-logger-cli",
+    version,
+    about = "离线分析 kovi-plugin-msg-logger 的消息数据库"
+)]
+struct Cli {
+    /// 插件数据目录，读取其中的 config.toml 与 msg_history.sqlite（或 database_url 指向的数据库）
+    #[arg(long, default_value = "data/kovi-plugin-msg-logger")]
+    data_dir: PathBuf,
+    /// 直接指定数据库文件，优先于 --data-dir
+    #[arg(long)]
+    db: Option<PathBuf>,
+    /// 以 JSON 输出结果
+    #[arg(long, global = true)]
+    json: bool,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// 热词排行
+    WordCloud {
+        #[command(flatten)]
+        scope: Scope,
+        #[arg(long, default_value_t = 50)]
+        limit: u64,
+    },
+    /// 活跃用户排行
+    TopTalkers {
+        #[command(flatten)]
+        scope: Scope,
+        #[arg(long, default_value_t = 10)]
+        limit: u64,
+    },
+    /// 按小时（或星期×小时）统计消息量
+    Heatmap {
+        #[command(flatten)]
+        scope: Scope,
+        /// 输出 星期×小时 分布
+        #[arg(long)]
+        weekly: bool,
+    },
+    /// 在群或私聊中搜索包含关键词的消息（需指定 --group 或 --peer）
+    Search {
+        #[command(flatten)]
+        scope: Scope,
+        keyword: String,
+        #[arg(long, default_value_t = 20)]
+        limit: u64,
+    },
+    /// 单个用户的发言统计，指定单个 --group 时附带群内排名
+    UserStats {
+        user_id: i64,
+        #[command(flatten)]
+        scope: Scope,
+    },
+    /// 导出消息记录（jsonl / csv / html），不支持 --bot
+    Export {
+        #[command(flatten)]
+        scope: Scope,
+        #[arg(long, default_value = "jsonl")]
+        format: String,
+        /// 输出文件，缺省时写到标准输出
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+    /// 整理数据库文件，回收空间
+    Vacuum,
+    /// 按当前配置重新分词，生成全部关键词
+    RebuildKeywords,
+}
+
+/// 查询范围，对应 `MessageFilter`
+#[derive(Args)]
+struct Scope {
+    #[arg(long)]
+    group: Option<i64>,
+    /// 私聊对端 QQ
+    #[arg(long)]
+    peer: Option<i64>,
+    #[arg(long)]
+    user: Option<i64>,
+    /// 最近 N 天
+    #[arg(long, conflicts_with_all = ["from", "to"])]
+    days: Option<i64>,
+    /// 起始日期 YYYY-MM-DD（含）
+    #[arg(long, requires = "to")]
+    from: Option<NaiveDate>,
+    /// 结束日期 YYYY-MM-DD（含）
+    #[arg(long, requires = "from")]
+    to: Option<NaiveDate>,
+    /// 仅统计该 Bot 账号记录的数据
+    #[arg(long)]
+    bot: Option<i64>,
+}
+
+impl Scope {
+    fn filter(&self) -> MessageFilter {
+        let mut f = MessageFilter::new();
+        if let Some(id) = self.group {
+            f = f.group(id);
+        }
+        if let Some(id) = self.peer {
+            f = f.peer(id);
+        }
+        if let Some(id) = self.user {
+            f = f.user(id);
+        }
+        match (self.from, self.to, self.days) {
+            (Some(from), Some(to), _) => f.date_range(from, to),
+            (_, _, Some(days)) => f.last_days(days),
+            _ => f,
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(cli).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// 修改数据库的命令；其余命令只读打开，不迁移也不重算时间列
+fn writes(command: &Command) -> bool {
+    matches!(command, Command::Vacuum | Command::RebuildKeywords)
+}
+
+/// SQLite URL 改为指定的打开模式，其他数据库原样返回
+fn with_sqlite_mode(url: &str, mode: &str) -> String {
+    if !url.starts_with("sqlite:") || url.contains(":memory:") {
+        return url.to_string();
+    }
+    let (base, params) = url.split_once('?').unwrap_or((url, ""));
+    let mut params: Vec<&str> = params
+        .split('&')
+        .filter(|p| !p.is_empty() && !p.starts_with("mode="))
+        .collect();
+    let mode = format!("mode={mode}");
+    params.push(&mode);
+    format!("{base}?{}", params.join("&"))
+}
+
+async fn open(cli: &Cli) -> Result<Logger, LoggerError> {
+    let read_only = !writes(&cli.command);
+    let db_path = cli
+        .db
+        .clone()
+        .unwrap_or_else(|| cli.data_dir.join("msg_history.sqlite"));
+
+    // 优先使用数据库同目录的配置，保证时区与分词规则和插件一致
+    let config_path = db_path
+        .parent()
+        .unwrap_or(Path::new("."))
+        .join("config.toml");
+    let config = if config_path.is_file() {
+        Config::read(&config_path).map_err(LoggerError::Config)?
+    } else {
+        Config::default()
+    };
+
+    let url = match config.database_url.trim() {
+        url if cli.db.is_none() && !url.is_empty() => url.to_string(),
+        _ if !db_path.is_file() => {
+            return Err(LoggerError::NotFound(format!(
+                "database {} does not exist",
+                db_path.display()
+            )));
+        }
+        _ => format!("sqlite://{}", db_path.display()),
+    };
+    let url = with_sqlite_mode(&url, if read_only { "ro" } else { "rw" });
+
+    LoggerBuilder::new()
+        .database_url(url)
+        .config(ConfigSnapshot::from_config(&config))
+        .max_connections(2)
+        .read_only(read_only)
+        .build()
+        .await
+}
+
+fn scoped(logger: &Logger, bot: Option<i64>) -> QueryApi {
+    match bot {
+        Some(id) => logger.query().for_bot(id),
+        None => logger.query().clone(),
+    }
+}
+
+async fn run(cli: Cli) -> Result<(), LoggerError> {
+    let logger = open(&cli).await?;
+    let json = cli.json;
+
+    match &cli.command {
+        Command::WordCloud { scope, limit } => {
+            let words = scoped(&logger, scope.bot)
+                .word_cloud_filtered(&scope.filter(), *limit)
+                .await?;
+            output(json, &words, || {
+                table(
+                    &["词", "次数"],
+                    words
+                        .iter()
+                        .map(|w| vec![w.word.clone(), w.count.to_string()]),
+                )
+            })?;
+        }
+        Command::TopTalkers { scope, limit } => {
+            let users = scoped(&logger, scope.bot)
+                .top_talkers_filtered(&scope.filter(), *limit)
+                .await?;
+            output(json, &users, || {
+                table(
+                    &["排名", "QQ", "昵称", "消息数"],
+                    users.iter().enumerate().map(|(i, u)| {
+                        vec![
+                            (i + 1).to_string(),
+                            u.user_id.to_string(),
+                            u.nickname.clone(),
+                            u.message_count.to_string(),
+                        ]
+                    }),
+                )
+            })?;
+        }
+        Command::Heatmap { scope, weekly } => {
+            let query = scoped(&logger, scope.bot);
+            if *weekly {
+                let grid = query
+                    .weekly_hourly_heatmap_filtered(&scope.filter())
+                    .await?;
+                output(json, &grid, || {
+                    let days = ["日", "一", "二", "三", "四", "五", "六"];
+                    let mut headers = vec!["星期".to_string()];
+                    headers.extend((0..24).map(|h| h.to_string()));
+                    let headers: Vec<&str> = headers.iter().map(String::as_str).collect();
+                    table(
+                        &headers,
+                        grid.iter().zip(days).map(|(row, day)| {
+                            std::iter::once(day.to_string())
+                                .chain(row.iter().map(|c| c.to_string()))
+                                .collect()
+                        }),
+                    )
+                })?;
+            } else {
+                let hours = query.hourly_heatmap_filtered(&scope.filter()).await?;
+                output(json, &hours, || {
+                    let max = hours.iter().map(|h| h.count).max().unwrap_or(0).max(1);
+                    table(
+                        &["小时", "消息数", ""],
+                        hours.iter().map(|h| {
+                            vec![
+                                format!("{:02}", h.hour),
+                                h.count.to_string(),
+                                "█".repeat((h.count * 40 / max) as usize),
+                            ]
+                        }),
+                    )
+                })?;
+            }
+        }
+        Command::Search {
+            scope,
+            keyword,
+            limit,
+        } => {
+            if scope.group.is_none() && scope.peer.is_none() {
+                return Err(LoggerError::InvalidInput(
+                    "search requires --group or --peer".to_string(),
+                ));
+            }
+            let query = scoped(&logger, scope.bot);
+            let found = query
+                .search_messages_filtered(&scope.filter(), keyword, *limit)
+                .await?;
+            output(json, &found, || {
+                table(
+                    &["时间", "QQ", "昵称", "内容"],
+                    found.iter().map(|m| message_row(&query, m)),
+                )
+            })?;
+        }
+        Command::UserStats { user_id, scope } => {
+            let query = scoped(&logger, scope.bot);
+            let stats = query.user_stats_filtered(*user_id, &scope.filter()).await?;
+            output(json, &stats, || {
+                let optional = |v: Option<i64>| v.map(|v| v.to_string()).unwrap_or_default();
+                table(
+                    &["项目", "值"],
+                    [
+                        ("QQ", stats.user_id.to_string()),
+                        ("昵称", stats.nickname.clone()),
+                        ("消息数", stats.total_messages.to_string()),
+                        ("关键词数", stats.total_words.to_string()),
+                        ("平均字数", format!("{:.1}", stats.avg_msg_length)),
+                        ("首次发言", format_time(&query, stats.first_seen)),
+                        ("最近发言", format_time(&query, stats.last_seen)),
+                        ("活跃天数", stats.active_days.to_string()),
+                        ("最活跃时段", optional(stats.favorite_hour.map(i64::from))),
+                        ("群内排名", optional(stats.rank_in_group)),
+                    ]
+                    .into_iter()
+                    .map(|(k, v)| vec![k.to_string(), v]),
+                )
+            })?;
+        }
+        Command::Export {
+            scope,
+            format,
+            output: path,
+        } => {
+            if scope.bot.is_some() {
+                return Err(LoggerError::InvalidInput(
+                    "export does not support --bot".to_string(),
+                ));
+            }
+            let format = ExportFormat::parse(format).ok_or_else(|| {
+                LoggerError::InvalidInput(format!("unknown export format: {format}"))
+            })?;
+            let filter = scope.filter();
+            let count = match path {
+                Some(path) => {
+                    let file = tokio::fs::File::create(path).await?;
+                    logger.export(&filter, format, file).await?
+                }
+                None => logger.export(&filter, format, tokio::io::stdout()).await?,
+            };
+            eprintln!("已导出 {count} 条消息");
+        }
+        Command::Vacuum => {
+            // 以数据库自身报告的大小为准，database_url 指向服务端数据库时同样适用
+            let before = logger.health().await?.db_bytes;
+            logger.vacuum().await?;
+            eprintln!(
+                "整理完成：{} → {}",
+                human_size(before),
+                human_size(logger.health().await?.db_bytes)
+            );
+        }
+        Command::RebuildKeywords => {
+            let total = logger
+                .rebuild_keywords(|n| eprint!("\r已处理 {n} 条消息"))
+                .await?;
+            eprintln!("\n重新分词完成，共 {total} 条消息");
+        }
+    }
+
+    logger.flush().await;
+    Ok(())
+}
+
+/// 按 --json 选择输出格式
+fn output<T: Serialize>(
+    json: bool,
+    value: &T,
+    render: impl FnOnce() -> String,
+) -> Result<(), LoggerError> {
+    let text = if json {
+        serde_json::to_string_pretty(value).map_err(|e| LoggerError::Internal(e.to_string()))?
+    } else {
+        render()
+    };
+    let mut stdout = std::io::stdout().lock();
+    writeln!(stdout, "{text}")?;
+    Ok(())
+}
+
+fn message_row(query: &QueryApi, m: &messages::Model) -> Vec<String> {
+    let mut text: String = m.clean_text.replace('\n', " ").chars().take(60).collect();
+    if m.clean_text.chars().count() > 60 {
+        text.push('…');
+    }
+    vec![
+        format_time(query, m.created_at),
+        m.user_id.to_string(),
+        m.sender_card
+            .clone()
+            .filter(|c| !c.is_empty())
+            .unwrap_or_else(|| m.sender_nickname.clone()),
+        text,
+    ]
+}
+
+fn format_time(query: &QueryApi, ts: i64) -> String {
+    query
+        .timezone()
+        .local_datetime(ts)
+        .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
+        .unwrap_or_else(|| ts.to_string())
+}
+
+/// 终端显示宽度，中日韩字符按两列计算
+fn display_width(s: &str) -> usize {
+    s.chars().map(|c| if c.is_ascii() { 1 } else { 2 }).sum()
+}
+
+/// 渲染左对齐的纯文本表格
+fn table(headers: &[&str], rows: impl IntoIterator<Item = Vec<String>>) -> String {
+    let rows: Vec<Vec<String>> = rows.into_iter().collect();
+    let mut widths: Vec<usize> = headers.iter().map(|h| display_width(h)).collect();
+    for row in &rows {
+        for (w, cell) in widths.iter_mut().zip(row) {
+            *w = (*w).max(display_width(cell));
+        }
+    }
+
+    let render = |cells: &mut dyn Iterator<Item = &str>| {
+        let line: Vec<String> = cells
+            .zip(&widths)
+            .map(|(cell, w)| format!("{cell}{}", " ".repeat(w - display_width(cell))))
+            .collect();
+        line.join("  ").trim_end().to_string()
+    };
+
+    let mut out = vec![render(&mut headers.iter().copied())];
+    out.push(
+        widths
+            .iter()
+            .map(|w| "-".repeat(*w))
+            .collect::<Vec<_>>()
+            .join("  "),
+    );
+    for row in &rows {
+        out.push(render(&mut row.iter().map(String::as_str)));
+    }
+    out.join("\n")
+}
+
+fn human_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{size:.1} {}", UNITS[unit])
+}
@@ -0,0 +1,190 @@
+//! 通过过滤条件传入恶意字符串，确认查询均已参数化
+
+mod common;
+
+use common::{GROUP, NOW, scratch_dir};
+use kovi_plugin_msg_logger::config;
+use kovi_plugin_msg_logger::db::{Logger, LoggerError, MessageFilter};
+use sea_orm::{ConnectionTrait, Database, DbBackend, Statement};
+
+const HOSTILE: &[&str] = &[
+    "' OR '1'='1",
+    "admin'; DROP TABLE messages; --",
+    "\" OR \"\"=\"",
+    "%",
+    "_",
+    "\\",
+    "member') UNION SELECT 1,2,3 --",
+    "\0",
+];
+
+async fn setup(name: &str) -> Logger {
+    let dir = scratch_dir(name);
+    let _ = config::CONFIG.set(config::Config::load(dir.clone()).unwrap());
+    let logger = Logger::new(dir.clone()).await.unwrap();
+
+    let url = format!(
+        "sqlite://{}?mode=rwc",
+        dir.join("msg_history.sqlite").to_string_lossy()
+    );
+    let db = Database::connect(url).await.unwrap();
+    let rows = [
+        (1, 111, "admin", "hello world", false),
+        (2, 222, "member", "100% sure", true),
+        (3, 333, "owner", "under_score", false),
+    ];
+    for (message_id, user_id, role, text, has_image) in rows {
+        db.execute(Statement::from_sql_and_values(
+            DbBackend::Sqlite,
+            "INSERT INTO users (user_id, nickname, first_seen, last_seen, message_count) \
+             VALUES (?, 'n', ?, ?, 1)",
+            [user_id.into(), NOW.into(), NOW.into()],
+        ))
+        .await
+        .unwrap();
+        db.execute(Statement::from_sql_and_values(
+            DbBackend::Sqlite,
+            "INSERT INTO messages (self_id, message_id, user_id, group_id, msg_type, raw_json, \
+             clean_text, text_length, has_image, has_at, is_reply, sender_nickname, sender_role, \
+             created_at, hour_of_day, day_of_week, local_date) \
+             VALUES (1, ?, ?, ?, 'group', '{}', ?, ?, ?, 0, 0, 'n', ?, ?, 12, 3, '2023-11-14')",
+            [
+                message_id.into(),
+                user_id.into(),
+                GROUP.into(),
+                text.into(),
+                (text.chars().count() as i32).into(),
+                has_image.into(),
+                role.into(),
+                (NOW + message_id).into(),
+            ],
+        ))
+        .await
+        .unwrap();
+    }
+    logger
+}
+
+fn window() -> MessageFilter {
+    MessageFilter::new()
+        .group(GROUP)
+        .time_range(NOW - 100, NOW + 100)
+}
+
+#[tokio::test]
+async fn hostile_filter_values_match_nothing() {
+    let logger = setup("filters").await;
+    let query = logger.query();
+
+    assert_eq!(query.message_count_filtered(&window()).await.unwrap(), 3);
+
+    for value in HOSTILE {
+        let by_role = window().roles([*value]);
+        assert_eq!(query.message_count_filtered(&by_role).await.unwrap(), 0);
+
+        let by_type = window().msg_type(*value);
+        assert_eq!(query.message_count_filtered(&by_type).await.unwrap(), 0);
+
+        let by_sub_type = window().sub_type(*value);
+        let stats = query
+            .message_type_stats_filtered(&by_sub_type)
+            .await
+            .unwrap();
+        assert_eq!(stats.total, 0);
+
+        let page = query.messages_page(&by_role, None, 10).await.unwrap();
+        assert!(page.items.is_empty());
+    }
+
+    // 表未被破坏，合法条件仍然可用
+    let admins = window().roles(["admin", "owner"]);
+    assert_eq!(query.message_count_filtered(&admins).await.unwrap(), 2);
+    let images = window().has_image(true);
+    assert_eq!(query.message_count_filtered(&images).await.unwrap(), 1);
+    assert_eq!(query.storage_stats().await.total_messages, 3);
+}
+
+#[tokio::test]
+async fn search_treats_wildcards_literally() {
+    let logger = setup("search").await;
+    let query = logger.query();
+
+    let hits = |kw: &'static str| async move {
+        query
+            .search_messages(GROUP, kw, 10)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|m| m.message_id)
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(hits("%").await, vec![2]);
+    assert_eq!(hits("_").await, vec![3]);
+    assert!(hits("' OR '1'='1").await.is_empty());
+    assert!(hits("\\").await.is_empty());
+    assert_eq!(hits("hello").await, vec![1]);
+}
+
+#[tokio::test]
+async fn user_queries_and_search_honor_filters() {
+    let logger = setup("user-filters").await;
+    let query = logger.query();
+    let later = MessageFilter::new()
+        .group(GROUP)
+        .time_range(NOW + 1000, NOW + 2000);
+
+    let found = query
+        .search_messages_filtered(&window().roles(["member"]), "%", 10)
+        .await
+        .unwrap();
+    assert_eq!(found.len(), 1);
+    assert!(
+        query
+            .search_messages_filtered(&window().roles(["admin"]), "%", 10)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+
+    let stats = query.user_stats_filtered(222, &window()).await.unwrap();
+    assert_eq!((stats.total_messages, stats.rank_in_group), (1, Some(1)));
+    for filter in [window().has_image(false), later.clone()] {
+        assert!(matches!(
+            query.user_stats_filtered(222, &filter).await,
+            Err(LoggerError::NotFound(_))
+        ));
+    }
+
+    assert_eq!(
+        query
+            .user_group_activity_filtered(111, &window())
+            .await
+            .unwrap(),
+        vec![(GROUP, 1)]
+    );
+    assert!(
+        query
+            .user_group_activity_filtered(111, &later)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+
+    let owner = window().roles(["owner"]);
+    assert_eq!(
+        query
+            .user_messages_filtered(333, &owner, 10)
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+    assert!(
+        query
+            .user_messages_filtered(333, &window().roles(["member"]), 10)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
@@ -0,0 +1,51 @@
+//! 查询结果缓存：重复查询命中，写入只让相关群的缓存失效
+
+mod common;
+
+use common::{GROUP, MEMORY, NOW, message, open};
+use kovi_plugin_msg_logger::db::{IncomingMessage, Logger, MessageFilter};
+
+const OTHER: i64 = GROUP + 1;
+
+async fn write(logger: &Logger, message_id: i64, group_id: i64) {
+    logger
+        .log_record(IncomingMessage {
+            group_id: Some(group_id),
+            ..message(message_id, 7, "alice", "你好", NOW + message_id)
+        })
+        .await
+        .unwrap();
+    logger.flush().await;
+}
+
+#[tokio::test]
+async fn writes_invalidate_only_their_group() {
+    let logger = open(MEMORY).await;
+    write(&logger, 1, GROUP).await;
+    write(&logger, 2, OTHER).await;
+
+    let api = logger.query();
+    let group = MessageFilter::new().group(GROUP);
+    let other = MessageFilter::new().group(OTHER);
+    assert_eq!(api.message_count_filtered(&group).await.unwrap(), 1);
+    assert_eq!(api.message_count_filtered(&other).await.unwrap(), 1);
+    let before = api.cache_stats();
+    assert_eq!(api.message_count_filtered(&group).await.unwrap(), 1);
+    assert_eq!(api.cache_stats().hits, before.hits + 1);
+
+    // 其他群的写入不影响本群的缓存
+    write(&logger, 3, OTHER).await;
+    let before = api.cache_stats();
+    assert_eq!(api.message_count_filtered(&group).await.unwrap(), 1);
+    assert_eq!(api.cache_stats().hits, before.hits + 1);
+    assert!(before.invalidations > 0);
+
+    // 本群写入后重新查询，得到新结果
+    write(&logger, 4, GROUP).await;
+    let before = api.cache_stats();
+    assert_eq!(api.message_count_filtered(&group).await.unwrap(), 2);
+    assert_eq!(api.message_count_filtered(&other).await.unwrap(), 2);
+    let after = api.cache_stats();
+    assert_eq!(after.hits, before.hits);
+    assert_eq!(after.misses, before.misses + 2);
+}
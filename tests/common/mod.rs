@@ -0,0 +1,122 @@
+//! 集成测试共用的临时目录、Logger 与消息构造
+#![allow(dead_code)]
+
+use kovi_plugin_msg_logger::config::ConfigSnapshot;
+use kovi_plugin_msg_logger::db::{IncomingMessage, Logger, LoggerBuilder};
+use sea_orm::{ConnectionTrait, DatabaseConnection, Statement};
+use serde_json::json;
+use std::path::PathBuf;
+use std::time::Duration;
+
+pub const GROUP: i64 = 10001;
+pub const NOW: i64 = 1_700_000_000;
+pub const MEMORY: &str = "sqlite::memory:";
+
+/// 每个用例独占的临时目录，已有内容会被清空
+pub fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("msg-logger-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// 临时目录下 SQLite 数据库的 URL
+pub fn db_url(name: &str) -> String {
+    format!(
+        "sqlite://{}?mode=rwc",
+        scratch_dir(name).join("msg_history.sqlite").display()
+    )
+}
+
+/// 使用默认配置打开 Logger
+pub async fn open(url: &str) -> Logger {
+    open_with(url, ConfigSnapshot::default()).await
+}
+
+pub async fn open_with(url: &str, config: ConfigSnapshot) -> Logger {
+    LoggerBuilder::new()
+        .database_url(url)
+        .config(config)
+        .build()
+        .await
+        .unwrap()
+}
+
+/// `GROUP` 内的一条纯文本消息
+pub fn message(
+    message_id: i64,
+    user_id: i64,
+    nickname: &str,
+    text: &str,
+    time: i64,
+) -> IncomingMessage {
+    IncomingMessage {
+        self_id: 1,
+        message_id,
+        user_id,
+        group_id: Some(GROUP),
+        text: text.to_string(),
+        segments: json!([{"type": "text", "data": {"text": text}}]),
+        sender_nickname: nickname.to_string(),
+        time,
+        ..Default::default()
+    }
+}
+
+/// 以当前时间写入一条群消息
+pub async fn say(logger: &Logger, message_id: i64, user_id: i64, group_id: i64, text: &str) {
+    logger
+        .log_record(IncomingMessage {
+            group_id: Some(group_id),
+            ..message(
+                message_id,
+                user_id,
+                "n",
+                text,
+                kovi::chrono::Local::now().timestamp(),
+            )
+        })
+        .await
+        .unwrap();
+}
+
+/// 读取 `logger_meta` 中的一项
+pub async fn meta(db: &DatabaseConnection, key: &str) -> Option<String> {
+    db.query_one(Statement::from_sql_and_values(
+        db.get_database_backend(),
+        "SELECT value FROM logger_meta WHERE key = ?",
+        [key.into()],
+    ))
+    .await
+    .unwrap()
+    .map(|r| r.try_get("", "value").unwrap())
+}
+
+/// 等待后台任务把 `logger_meta` 中的一项写为期望值
+pub async fn wait_for_meta(db: &DatabaseConnection, key: &str, value: &str) {
+    for _ in 0..200 {
+        if meta(db, key).await.as_deref() == Some(value) {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert_eq!(meta(db, key).await.as_deref(), Some(value));
+}
+
+/// 按写入顺序返回每条消息的 (hour_of_day, local_date)
+pub async fn time_columns(db: &DatabaseConnection) -> Vec<(i32, String)> {
+    db.query_all(Statement::from_string(
+        db.get_database_backend(),
+        "SELECT hour_of_day, local_date FROM messages ORDER BY id",
+    ))
+    .await
+    .unwrap()
+    .into_iter()
+    .map(|r| {
+        (
+            r.try_get("", "hour_of_day").unwrap(),
+            r.try_get("", "local_date").unwrap(),
+        )
+    })
+    .collect()
+}
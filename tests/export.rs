@@ -0,0 +1,119 @@
+//! CSV 导出对群成员可控文本的处理
+
+mod common;
+
+use common::{GROUP, MEMORY, open};
+use kovi_plugin_msg_logger::db::{ExportFormat, IncomingMessage, MessageFilter};
+use serde_json::json;
+
+#[tokio::test]
+async fn csv_neutralizes_formula_cells() {
+    let logger = open(MEMORY).await;
+    let text = "=HYPERLINK(\"http://x\",\"点我\")";
+    logger
+        .log_record(IncomingMessage {
+            self_id: 1,
+            message_id: 1,
+            user_id: 111,
+            group_id: Some(GROUP),
+            text: text.to_string(),
+            segments: json!([{"type": "text", "data": {"text": text}}]),
+            sender_nickname: "@everyone".to_string(),
+            sender_card: Some("+1".to_string()),
+            time: 1_700_000_000,
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    logger.flush().await;
+
+    let mut out = Vec::new();
+    let count = logger
+        .export(
+            &MessageFilter::new().group(GROUP),
+            ExportFormat::Csv,
+            &mut out,
+        )
+        .await
+        .unwrap();
+    assert_eq!(count, 1);
+
+    let csv = String::from_utf8(out).unwrap();
+    let row = csv.lines().nth(1).unwrap();
+    assert!(row.contains(",'@everyone,'+1,"));
+    assert!(row.contains("\"'=HYPERLINK(\"\"http://x\"\",\"\"点我\"\")\""));
+    assert!(!row.contains(",=HYPERLINK"));
+}
+
+/// 消息交错分布在多个群与月份，且超过一页
+#[cfg(feature = "parquet")]
+#[tokio::test]
+async fn parquet_partitions_are_written_once() {
+    use kovi_plugin_msg_logger::config::{ConfigSnapshot, TimeZoneSetting};
+    use kovi_plugin_msg_logger::db::ParquetPartitioning;
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+
+    let logger = common::open_with(
+        MEMORY,
+        ConfigSnapshot {
+            timezone: TimeZoneSetting::parse("+00:00").unwrap(),
+            ..ConfigSnapshot::default()
+        },
+    )
+    .await;
+    // 2024-01-01 00:00 UTC 起每小时一条，跨越一月与二月
+    const JAN1: i64 = 1_704_067_200;
+    for i in 0..1500i64 {
+        let group_id = match i % 3 {
+            0 => Some(GROUP),
+            1 => Some(GROUP + 1),
+            _ => None,
+        };
+        logger
+            .log_record(IncomingMessage {
+                self_id: 1,
+                message_id: i + 1,
+                user_id: 100 + i % 5,
+                group_id,
+                text: "测试消息".to_string(),
+                sender_nickname: "tester".to_string(),
+                time: JAN1 + i * 3600,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+    }
+    logger.flush().await;
+
+    let dir = common::scratch_dir("parquet");
+    let report = logger
+        .export_parquet(&MessageFilter::new(), &dir, ParquetPartitioning::GroupMonth)
+        .await
+        .unwrap();
+    assert_eq!(report.messages, 1500);
+    assert_eq!(report.users, 5);
+
+    // 3 个会话 × 3 个月（1500 小时约 62.5 天）× 2 张表 + 用户表
+    let mut files = report.files.clone();
+    files.sort();
+    files.dedup();
+    assert_eq!(files.len(), report.files.len());
+    assert_eq!(files.len(), 3 * 3 * 2 + 1);
+
+    let rows: i64 = report
+        .files
+        .iter()
+        .filter(|p| p.starts_with(dir.join("messages")))
+        .map(|p| {
+            let reader = SerializedFileReader::new(std::fs::File::open(p).unwrap()).unwrap();
+            reader.metadata().file_metadata().num_rows()
+        })
+        .sum();
+    assert_eq!(rows, 1500);
+    assert!(
+        dir.join("messages/group=0/month=2024-02/data.parquet")
+            .is_file()
+    );
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
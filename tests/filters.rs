@@ -0,0 +1,110 @@
+//! 组合过滤条件：各分析接口接受同一个 `MessageFilter`
+
+mod common;
+
+use common::{GROUP, MEMORY, NOW, message, open_with};
+use kovi_plugin_msg_logger::config::{ConfigSnapshot, TimeZoneSetting};
+use kovi_plugin_msg_logger::db::{IncomingMessage, MessageDirection, MessageFilter, QueryApi};
+use serde_json::json;
+
+const BOT: i64 = 1;
+const HOUR: i64 = 3600;
+/// `NOW` 为 +08:00 的 06:13
+const FIRST_HOUR: i32 = 6;
+
+fn sent_by(role: &str, record: IncomingMessage) -> IncomingMessage {
+    IncomingMessage {
+        sender_role: Some(role.to_string()),
+        ..record
+    }
+}
+
+fn image(message_id: i64, user_id: i64, time: i64) -> IncomingMessage {
+    IncomingMessage {
+        segments: json!([{"type": "image", "data": {"file": "a.jpg"}}]),
+        ..message(message_id, user_id, "n", "[图片]", time)
+    }
+}
+
+async fn hours(api: &QueryApi, filter: &MessageFilter) -> Vec<(i32, i64)> {
+    api.hourly_heatmap_filtered(filter)
+        .await
+        .unwrap()
+        .into_iter()
+        .filter(|h| h.count > 0)
+        .map(|h| (h.hour, h.count))
+        .collect()
+}
+
+#[tokio::test]
+async fn composed_filters_narrow_every_analytic() {
+    let config = ConfigSnapshot {
+        timezone: TimeZoneSetting::parse("+08:00").unwrap(),
+        ..ConfigSnapshot::default()
+    };
+    let logger = open_with(MEMORY, config).await;
+    let mut reply = message(5, 8, "bob", "好的收到了", NOW + 2 * HOUR);
+    reply.segments = json!([
+        {"type": "reply", "data": {"id": "1"}},
+        {"type": "at", "data": {"qq": "7"}},
+        {"type": "text", "data": {"text": "好的收到了"}}
+    ]);
+    let records = [
+        sent_by("admin", image(1, 7, NOW)),
+        sent_by("admin", image(2, 7, NOW + HOUR)),
+        sent_by(
+            "admin",
+            message(3, 7, "alice", "今天的会议改到下午三点", NOW + HOUR),
+        ),
+        sent_by("member", image(4, 8, NOW + HOUR)),
+        sent_by("member", reply),
+        sent_by(
+            "owner",
+            IncomingMessage {
+                direction: MessageDirection::Sent,
+                ..image(6, BOT, NOW + 2 * HOUR)
+            },
+        ),
+    ];
+    for record in records {
+        logger.log_record(record).await.unwrap();
+    }
+    logger.flush().await;
+
+    let api = logger.query();
+    let group = MessageFilter::new().group(GROUP);
+    let hour = |offset: i32| FIRST_HOUR + offset;
+
+    // 管理员发的图片
+    let admin_images = group.clone().roles(["admin", "owner"]).has_image(true);
+    assert_eq!(api.message_count_filtered(&admin_images).await.unwrap(), 3);
+    assert_eq!(
+        hours(api, &admin_images).await,
+        vec![(hour(0), 1), (hour(1), 1), (hour(2), 1)]
+    );
+    let without_bot = admin_images.clone().exclude_bot();
+    assert_eq!(api.message_count_filtered(&without_bot).await.unwrap(), 2);
+    let talkers = api.top_talkers_filtered(&without_bot, 10).await.unwrap();
+    assert_eq!(
+        talkers
+            .iter()
+            .map(|t| (t.user_id, t.message_count))
+            .collect::<Vec<_>>(),
+        vec![(7, 2)]
+    );
+
+    // 长文本、回复与 @
+    let long = group.clone().has_image(false).min_length(6);
+    let talkers = api.top_talkers_filtered(&long, 10).await.unwrap();
+    assert_eq!(
+        talkers.iter().map(|t| t.user_id).collect::<Vec<_>>(),
+        vec![7]
+    );
+    let replies = group.clone().is_reply(true).has_at(true).max_length(5);
+    assert_eq!(hours(api, &replies).await, vec![(hour(2), 1)]);
+    let types = api
+        .message_type_stats_filtered(&group.exclude_bot())
+        .await
+        .unwrap();
+    assert_eq!(types.with_image, 3);
+}
@@ -0,0 +1,50 @@
+//! HTTP 接口的令牌校验
+#![cfg(feature = "http")]
+
+mod common;
+
+use common::{MEMORY, open};
+use kovi_plugin_msg_logger::http;
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+const TOKEN: &str = "s3cr+t&=/";
+
+/// 发送请求并返回状态码
+async fn status(addr: std::net::SocketAddr, target: &str, bearer: Option<&str>) -> u16 {
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    let auth = bearer
+        .map(|t| format!("Authorization: Bearer {t}\r\n"))
+        .unwrap_or_default();
+    let request =
+        format!("GET {target} HTTP/1.1\r\nHost: localhost\r\n{auth}Connection: close\r\n\r\n");
+    stream.write_all(request.as_bytes()).await.unwrap();
+
+    // 实时流不会结束，只读取状态行
+    let mut buf = [0u8; 12];
+    stream.read_exact(&mut buf).await.unwrap();
+    std::str::from_utf8(&buf[9..12]).unwrap().parse().unwrap()
+}
+
+#[tokio::test]
+async fn query_token_is_only_accepted_on_stream() {
+    let logger = open(MEMORY).await;
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let app = http::router(Arc::new(logger), TOKEN);
+    tokio::spawn(async move { axum::serve(listener, app).await });
+
+    let encoded = "s3cr%2Bt%26%3D%2F";
+    assert_eq!(status(addr, "/api/storage_stats", Some(TOKEN)).await, 200);
+    assert_eq!(status(addr, "/api/storage_stats", None).await, 401);
+    assert_eq!(
+        status(addr, &format!("/api/storage_stats?token={encoded}"), None).await,
+        401
+    );
+    assert_eq!(
+        status(addr, &format!("/api/stream?token={encoded}"), None).await,
+        200
+    );
+    assert_eq!(status(addr, "/api/stream?token=s3cr%2Bt", None).await, 401);
+}
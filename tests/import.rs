@@ -0,0 +1,132 @@
+//! 导入 QQ 导出的消息记录（fixtures 目录下的样例文件）
+
+mod common;
+
+use common::{GROUP, MEMORY, NOW, open, scratch_dir};
+use kovi_plugin_msg_logger::db::{ImportOptions, MessageFilter};
+use serde_json::json;
+use std::path::PathBuf;
+
+const BOT: i64 = 10000;
+
+fn fixture(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+}
+
+fn options() -> ImportOptions {
+    ImportOptions {
+        self_id: BOT,
+        group_id: Some(GROUP),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn txt_import_marks_bot_lines_as_sent() {
+    let logger = open(MEMORY).await;
+    let report = logger
+        .import_file(fixture("qq_group.txt"), &options(), |_| {})
+        .await
+        .unwrap();
+    assert_eq!(
+        (
+            report.total,
+            report.imported,
+            report.duplicates,
+            report.skipped
+        ),
+        (3, 2, 0, 1)
+    );
+
+    let items = logger
+        .query()
+        .messages_page(&MessageFilter::new().group(GROUP), None, 10)
+        .await
+        .unwrap()
+        .items;
+    assert_eq!(items.len(), 2);
+    assert_eq!(
+        (items[0].user_id, items[0].sub_type.as_deref()),
+        (123456, None)
+    );
+    assert_eq!(
+        (items[1].user_id, items[1].sub_type.as_deref()),
+        (BOT, Some("self_sent"))
+    );
+}
+
+#[tokio::test]
+async fn reimport_only_reports_duplicates() {
+    let logger = open(MEMORY).await;
+    let first = logger
+        .import_file(fixture("qq_group.mht"), &options(), |_| {})
+        .await
+        .unwrap();
+    assert_eq!((first.imported, first.duplicates, first.skipped), (2, 0, 0));
+
+    let again = logger
+        .import_file(fixture("qq_group.mht"), &options(), |_| {})
+        .await
+        .unwrap();
+    assert_eq!(
+        (again.total, again.imported, again.duplicates, again.skipped),
+        (2, 0, 2, 0)
+    );
+    let filter = MessageFilter::new().group(GROUP);
+    assert_eq!(
+        logger
+            .query()
+            .message_count_filtered(&filter)
+            .await
+            .unwrap(),
+        2
+    );
+}
+
+fn group_event(message_id: Option<i64>, text: &str) -> String {
+    let mut event = json!({
+        "post_type": "message",
+        "message_type": "group",
+        "group_id": GROUP,
+        "user_id": 7,
+        "time": NOW,
+        "message": [{"type": "text", "data": {"text": text}}],
+        "sender": {"nickname": "alice"},
+    });
+    if let Some(id) = message_id {
+        event["message_id"] = json!(id);
+    }
+    event.to_string()
+}
+
+#[tokio::test]
+async fn repeated_lines_in_one_file_count_as_duplicates() {
+    let long = "长".repeat(2000);
+    let lines = [
+        group_event(Some(1), "早上好"),
+        group_event(Some(1), "早上好"),
+        // 无消息 ID 时按时间与入库后的（截断）文本去重
+        group_event(None, &long),
+        group_event(None, &long),
+    ];
+    let path = scratch_dir("import-jsonl").join("events.jsonl");
+    std::fs::write(&path, lines.join("\n")).unwrap();
+
+    let logger = open(MEMORY).await;
+    let first = logger.import_file(&path, &options(), |_| {}).await.unwrap();
+    assert_eq!((first.imported, first.duplicates), (2, 2));
+    let again = logger.import_file(&path, &options(), |_| {}).await.unwrap();
+    assert_eq!((again.imported, again.duplicates), (0, 4));
+
+    let filter = MessageFilter::new().group(GROUP);
+    assert_eq!(
+        logger
+            .query()
+            .message_count_filtered(&filter)
+            .await
+            .unwrap(),
+        2
+    );
+}
@@ -0,0 +1,143 @@
+//! `Logger::log_record` 写入外部来源的消息
+
+mod common;
+
+use common::{GROUP, MEMORY, NOW, open};
+use kovi_plugin_msg_logger::db::{IncomingMessage, LoggerError, MessageDirection, MessageFilter};
+use serde_json::json;
+
+const BOT: i64 = 1;
+
+#[tokio::test]
+async fn private_messages_resolve_peer_and_direction() {
+    let logger = open(MEMORY).await;
+
+    // 收到的私聊：对端即发送者
+    logger
+        .log_record(IncomingMessage {
+            self_id: BOT,
+            message_id: 1,
+            user_id: 222,
+            text: "在吗".to_string(),
+            sender_nickname: "friend".to_string(),
+            time: NOW,
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    // 自己发出的私聊必须指明对端
+    let missing_peer = logger
+        .log_record(IncomingMessage {
+            self_id: BOT,
+            message_id: 2,
+            user_id: BOT,
+            direction: MessageDirection::Sent,
+            text: "在".to_string(),
+            time: NOW + 1,
+            ..Default::default()
+        })
+        .await;
+    assert!(matches!(missing_peer, Err(LoggerError::InvalidInput(_))));
+    logger
+        .log_record(IncomingMessage {
+            self_id: BOT,
+            message_id: 3,
+            user_id: BOT,
+            peer_id: Some(222),
+            direction: MessageDirection::Sent,
+            text: "在".to_string(),
+            time: NOW + 2,
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    logger.flush().await;
+
+    let conversation = logger
+        .query()
+        .messages_page(&MessageFilter::new().peer(222), None, 10)
+        .await
+        .unwrap()
+        .items;
+    assert_eq!(conversation.len(), 2);
+    assert_eq!(conversation[0].sub_type, None);
+    assert_eq!(conversation[1].sub_type.as_deref(), Some("self_sent"));
+    assert_eq!(conversation[1].raw_json, "[]");
+}
+
+#[tokio::test]
+async fn oversized_multibyte_text_is_truncated() {
+    let logger = open(MEMORY).await;
+    let text = "字".repeat(1400);
+    logger
+        .log_record(IncomingMessage {
+            self_id: BOT,
+            message_id: 1,
+            user_id: 111,
+            group_id: Some(GROUP),
+            segments: json!([{"type": "text", "data": {"text": "测".repeat(4000)}}]),
+            text,
+            sender_nickname: "n".to_string(),
+            time: NOW,
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    logger.flush().await;
+
+    let stored = logger
+        .query()
+        .messages_page(&MessageFilter::new().group(GROUP), None, 1)
+        .await
+        .unwrap()
+        .items
+        .remove(0);
+    assert!(stored.clean_text.ends_with("...(truncated)"));
+    assert!(stored.clean_text.len() <= 4000 + "...(truncated)".len());
+    assert!(stored.raw_json.ends_with("...(truncated)"));
+}
+
+#[tokio::test]
+async fn self_sent_messages_keep_bot_profile() {
+    let logger = open(MEMORY).await;
+    let sent = |message_id: i64, nickname: &str, time: i64| IncomingMessage {
+        self_id: BOT,
+        message_id,
+        user_id: BOT,
+        group_id: Some(GROUP),
+        direction: MessageDirection::Sent,
+        text: "收到".to_string(),
+        sender_nickname: nickname.to_string(),
+        sender_card: Some("值班机器人".to_string()),
+        sender_role: Some("admin".to_string()),
+        time,
+        ..Default::default()
+    };
+    logger.log_record(sent(1, "小助手", NOW)).await.unwrap();
+    logger
+        .log_record(IncomingMessage {
+            sender_role: Some("member".to_string()),
+            ..common::message(2, 222, "friend", "谢谢", NOW + 1)
+        })
+        .await
+        .unwrap();
+    logger.flush().await;
+
+    let group = MessageFilter::new().group(GROUP);
+    let api = logger.query();
+    let stored = api.messages_page(&group, None, 10).await.unwrap().items;
+    assert_eq!(stored[0].sender_nickname, "小助手");
+    assert_eq!(stored[0].sender_card.as_deref(), Some("值班机器人"));
+    assert_eq!(stored[0].sender_role.as_deref(), Some("admin"));
+    assert_eq!(stored[0].created_at, NOW);
+
+    let admins = group.clone().roles(["admin", "owner"]);
+    assert_eq!(api.message_count_filtered(&admins).await.unwrap(), 1);
+
+    // Bot 改名后，排行榜显示新昵称
+    logger.log_record(sent(3, "新名字", NOW + 2)).await.unwrap();
+    logger.flush().await;
+    let talkers = api.top_talkers_filtered(&group, 10).await.unwrap();
+    let bot = talkers.iter().find(|t| t.user_id == BOT).unwrap();
+    assert_eq!((bot.nickname.as_str(), bot.message_count), ("新名字", 2));
+}
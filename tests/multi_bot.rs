@@ -0,0 +1,70 @@
+//! 多个 Bot 账号共用一个库：群消息跨账号去重，查询可按账号限定
+
+mod common;
+
+use common::{GROUP, MEMORY, NOW, message, open};
+use kovi_plugin_msg_logger::db::{IncomingMessage, MessageFilter};
+use serde_json::json;
+
+fn seen_by(self_id: i64, record: IncomingMessage) -> IncomingMessage {
+    IncomingMessage { self_id, ..record }
+}
+
+fn private(self_id: i64, message_id: i64, peer_id: i64) -> IncomingMessage {
+    IncomingMessage {
+        self_id,
+        message_id,
+        user_id: peer_id,
+        peer_id: Some(peer_id),
+        text: "你好".to_string(),
+        segments: json!([{"type": "text", "data": {"text": "你好"}}]),
+        sender_nickname: "carol".to_string(),
+        time: NOW,
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn group_messages_are_shared_and_queries_scope_by_bot() {
+    let logger = open(MEMORY).await;
+    let records = [
+        // 两个账号都在群里，同一条消息只记一次
+        seen_by(1, message(1, 7, "alice", "大家好", NOW)),
+        seen_by(2, message(1, 7, "alice", "大家好", NOW)),
+        seen_by(2, message(2, 8, "bob", "晚上好", NOW + 60)),
+        // 私聊消息 ID 各账号独立
+        private(1, 100, 9),
+        private(2, 100, 9),
+    ];
+    for record in records {
+        logger.log_record(record).await.unwrap();
+    }
+    logger.flush().await;
+
+    let all = logger.query();
+    let group = MessageFilter::new().group(GROUP);
+    assert_eq!(all.message_count_filtered(&group).await.unwrap(), 2);
+    assert_eq!(all.storage_stats().await.total_messages, 4);
+    let alice = all.user_stats(7, Some(GROUP)).await.unwrap();
+    assert_eq!(alice.total_messages, 1);
+
+    let first = all.for_bot(1);
+    assert_eq!(first.bot_scope(), Some(1));
+    assert_eq!(first.message_count_filtered(&group).await.unwrap(), 1);
+    assert_eq!(first.private_conversation(9, 10).await.unwrap().len(), 1);
+
+    let second = all.for_bot(2);
+    assert_eq!(second.message_count_filtered(&group).await.unwrap(), 1);
+    let talkers = second.top_talkers_filtered(&group, 10).await.unwrap();
+    assert_eq!(
+        talkers.iter().map(|t| t.user_id).collect::<Vec<_>>(),
+        vec![8]
+    );
+    assert_eq!(second.private_conversation(9, 10).await.unwrap().len(), 1);
+
+    assert_eq!(all.private_conversation(9, 10).await.unwrap().len(), 2);
+    assert_eq!(
+        all.for_bot(3).message_count_filtered(&group).await.unwrap(),
+        0
+    );
+}
@@ -0,0 +1,72 @@
+//! 键集分页：同一时间戳的多条消息跨页既不遗漏也不重复
+
+mod common;
+
+use common::{GROUP, MEMORY, NOW, message, open};
+use futures::TryStreamExt;
+use kovi_plugin_msg_logger::db::{MessageFilter, PageCursor};
+
+/// 大部分消息落在同一秒，页边界必然切在相同的 created_at 上
+fn time_of(message_id: i64) -> i64 {
+    match message_id {
+        1..=2 => NOW - 1,
+        23.. => NOW + 1,
+        _ => NOW,
+    }
+}
+
+#[tokio::test]
+async fn cursors_walk_equal_timestamps_exactly_once() {
+    let logger = open(MEMORY).await;
+    // 乱序写入，分页顺序只取决于 (created_at, id)
+    for message_id in (1..=25).rev() {
+        let user_id = 7 + message_id % 2;
+        let record = message(message_id, user_id, "n", "同一秒", time_of(message_id));
+        logger.log_record(record).await.unwrap();
+    }
+    logger.flush().await;
+
+    let api = logger.query();
+    let group = MessageFilter::new().group(GROUP);
+    let mut seen = Vec::new();
+    let mut cursor: Option<String> = None;
+    loop {
+        let parsed = cursor.as_deref().map(|c| c.parse::<PageCursor>().unwrap());
+        let page = api.messages_page(&group, parsed, 4).await.unwrap();
+        assert!(page.items.len() <= 4);
+        seen.extend(page.items.iter().map(|m| (m.created_at, m.id)));
+        match page.next_cursor {
+            Some(next) => cursor = Some(next.to_string()),
+            None => break,
+        }
+    }
+    assert_eq!(seen.len(), 25);
+    assert!(seen.windows(2).all(|w| w[0] < w[1]), "{seen:?}");
+
+    let streamed = api
+        .messages_stream(group)
+        .map_ok(|m| (m.created_at, m.id))
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap();
+    assert_eq!(streamed, seen);
+
+    // 倒序分页同样覆盖该用户的全部消息
+    let mut newest_first = Vec::new();
+    let mut cursor = None;
+    loop {
+        let page = api
+            .user_messages_page(7, Some(GROUP), cursor, 3)
+            .await
+            .unwrap();
+        newest_first.extend(page.items.iter().map(|m| (m.created_at, m.id)));
+        cursor = page.next_cursor;
+        if cursor.is_none() {
+            break;
+        }
+    }
+    assert_eq!(newest_first.len(), 12);
+    assert!(newest_first.windows(2).all(|w| w[0] > w[1]));
+
+    assert!("x1.2".parse::<PageCursor>().is_err());
+}
@@ -0,0 +1,70 @@
+//! 私聊按对端记录，双向消息都能按对端查询
+
+mod common;
+
+use common::{MEMORY, open};
+use kovi_plugin_msg_logger::db::{IncomingMessage, MessageDirection};
+use serde_json::json;
+
+const BOT: i64 = 1;
+const FRIEND: i64 = 222;
+const STRANGER: i64 = 333;
+
+fn private(message_id: i64, user_id: i64, peer_id: i64, text: &str, time: i64) -> IncomingMessage {
+    IncomingMessage {
+        self_id: BOT,
+        message_id,
+        user_id,
+        peer_id: Some(peer_id),
+        direction: if user_id == BOT {
+            MessageDirection::Sent
+        } else {
+            MessageDirection::Received
+        },
+        text: text.to_string(),
+        segments: json!([{"type": "text", "data": {"text": text}}]),
+        sender_nickname: "n".to_string(),
+        time,
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn private_queries_include_both_directions() {
+    let logger = open(MEMORY).await;
+    let now = kovi::chrono::Local::now().timestamp();
+    let records = [
+        private(1, FRIEND, FRIEND, "周末去爬山吗", now - 3 * 86400),
+        private(2, BOT, FRIEND, "爬山可以", now - 3 * 86400 + 60),
+        private(3, FRIEND, FRIEND, "那就爬山", now - 60),
+        private(4, STRANGER, STRANGER, "爬山装备出售", now - 30),
+    ];
+    for record in records {
+        logger.log_record(record).await.unwrap();
+    }
+    logger.flush().await;
+    let query = logger.query();
+
+    let conversation = query.private_conversation(FRIEND, 10).await.unwrap();
+    assert_eq!(
+        conversation
+            .iter()
+            .map(|m| m.message_id)
+            .collect::<Vec<_>>(),
+        vec![1, 2, 3]
+    );
+    assert!(
+        conversation
+            .iter()
+            .all(|m| m.group_id.is_none() && m.peer_id == Some(FRIEND))
+    );
+    assert_eq!(conversation[1].user_id, BOT);
+
+    let words = query.private_word_cloud(FRIEND, 10, 7).await.unwrap();
+    let climb = words.iter().find(|w| w.word == "爬山").unwrap();
+    assert_eq!(climb.count, 3);
+
+    let trend = query.private_daily_trend(FRIEND, 7).await.unwrap();
+    assert_eq!(trend.iter().map(|d| d.count).sum::<i64>(), 3);
+    assert_eq!(trend.len(), 2);
+}
@@ -0,0 +1,67 @@
+//! 只读打开：不修改库文件，时区以库内记录为准
+
+mod common;
+
+use common::{GROUP, message, meta, open_with, scratch_dir, time_columns, wait_for_meta};
+use kovi_plugin_msg_logger::config::{ConfigSnapshot, TimeZoneSetting};
+use kovi_plugin_msg_logger::db::{LoggerBuilder, MessageFilter};
+use sea_orm::Database;
+use std::time::Duration;
+/// 2024-05-01 10:00 (+08:00)，即 02:00 UTC
+const DAY1: i64 = 1_714_528_800;
+
+#[tokio::test]
+async fn read_only_keeps_stored_timezone_and_columns() {
+    let dir = scratch_dir("read-only");
+    let path = dir.join("msg_history.sqlite");
+
+    let shanghai = ConfigSnapshot {
+        timezone: TimeZoneSetting::parse("+08:00").unwrap(),
+        ..ConfigSnapshot::default()
+    };
+    let logger = open_with(&format!("sqlite://{}?mode=rwc", path.display()), shanghai).await;
+    logger
+        .log_record(message(1, 7, "alice", "早上好", DAY1))
+        .await
+        .unwrap();
+    logger.flush().await;
+
+    let raw = Database::connect(format!("sqlite://{}?mode=ro", path.display()))
+        .await
+        .unwrap();
+    // 新库的时区记录由后台任务写入
+    wait_for_meta(&raw, "timezone", "+08:00").await;
+    let before = time_columns(&raw).await;
+    assert_eq!(before, vec![(10, "2024-05-01".to_string())]);
+    drop(logger);
+
+    // 配置的时区与库内不同，只读打开时不得重算
+    let utc = ConfigSnapshot {
+        timezone: TimeZoneSetting::parse("+00:00").unwrap(),
+        ..ConfigSnapshot::default()
+    };
+    let reader = LoggerBuilder::new()
+        .database_url(format!("sqlite://{}?mode=ro", path.display()))
+        .config(utc)
+        .read_only(true)
+        .build()
+        .await
+        .unwrap();
+    assert_eq!(reader.query().timezone().canonical(), "+08:00");
+    let heatmap = reader
+        .query()
+        .hourly_heatmap_filtered(&MessageFilter::new().group(GROUP))
+        .await
+        .unwrap();
+    assert_eq!(
+        heatmap.iter().find(|h| h.count > 0).map(|h| h.hour),
+        Some(10)
+    );
+
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert_eq!(meta(&raw, "timezone").await.as_deref(), Some("+08:00"));
+    assert_eq!(time_columns(&raw).await, before);
+
+    drop(reader);
+    std::fs::remove_dir_all(dir).unwrap();
+}
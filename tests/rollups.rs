@@ -0,0 +1,153 @@
+//! 预聚合表：整日区间读预聚合表，结果须与直接扫原始表一致
+
+mod common;
+
+use common::{GROUP, message, open_with, scratch_dir};
+use kovi::chrono::NaiveDate;
+use kovi_plugin_msg_logger::config::{ConfigSnapshot, TimeZoneSetting};
+use kovi_plugin_msg_logger::db::{IncomingMessage, MessageFilter, QueryApi};
+use sea_orm::{ConnectionTrait, Database, DatabaseConnection, Statement};
+/// 2024-05-01 00:00 (+08:00)
+const MAY1: i64 = 1_714_492_800;
+const HOUR: i64 = 3600;
+const DAY: i64 = 86400;
+const TEXTS: [&str; 3] = ["天气不错", "明天天气怎么样", "一起吃饭"];
+
+fn batch(first_id: i64, days: std::ops::Range<i64>) -> Vec<IncomingMessage> {
+    let mut id = first_id;
+    let mut records = Vec::new();
+    for day in days {
+        for hour in [1, 9, 13, 23] {
+            let user = 7 + (id % 3);
+            let text = TEXTS[(id % 3) as usize];
+            records.push(message(id, user, "n", text, MAY1 + day * DAY + hour * HOUR));
+            id += 1;
+        }
+    }
+    records
+}
+
+async fn count(db: &DatabaseConnection, table: &str) -> i64 {
+    db.query_one(Statement::from_string(
+        db.get_database_backend(),
+        format!("SELECT COUNT(*) AS n FROM {table}"),
+    ))
+    .await
+    .unwrap()
+    .unwrap()
+    .try_get("", "n")
+    .unwrap()
+}
+
+/// 同一过滤条件分别走预聚合表与原始表（排除 Bot 的条件会跳过预聚合表）
+async fn assert_same(api: &QueryApi, filter: MessageFilter) {
+    let raw = filter.clone().exclude_bot();
+
+    let trend = api.daily_trend_filtered(&filter).await.unwrap();
+    let expected = api.daily_trend_filtered(&raw).await.unwrap();
+    assert!(!trend.is_empty());
+    assert_eq!(
+        trend.iter().map(|d| (&d.date, d.count)).collect::<Vec<_>>(),
+        expected
+            .iter()
+            .map(|d| (&d.date, d.count))
+            .collect::<Vec<_>>()
+    );
+
+    let words = api.word_cloud_filtered(&filter, 10).await.unwrap();
+    let expected = api.word_cloud_filtered(&raw, 10).await.unwrap();
+    let mut words = words.iter().map(|w| (&w.word, w.count)).collect::<Vec<_>>();
+    let mut expected = expected
+        .iter()
+        .map(|w| (&w.word, w.count))
+        .collect::<Vec<_>>();
+    words.sort();
+    expected.sort();
+    assert_eq!(words, expected);
+
+    let talkers = api.top_talkers_filtered(&filter, 10).await.unwrap();
+    let expected = api.top_talkers_filtered(&raw, 10).await.unwrap();
+    assert_eq!(
+        talkers
+            .iter()
+            .map(|t| (t.user_id, t.message_count))
+            .collect::<Vec<_>>(),
+        expected
+            .iter()
+            .map(|t| (t.user_id, t.message_count))
+            .collect::<Vec<_>>()
+    );
+
+    let heatmap = api.hourly_heatmap_filtered(&filter).await.unwrap();
+    let expected = api.hourly_heatmap_filtered(&raw).await.unwrap();
+    assert_eq!(
+        heatmap
+            .iter()
+            .map(|h| (h.hour, h.count))
+            .collect::<Vec<_>>(),
+        expected
+            .iter()
+            .map(|h| (h.hour, h.count))
+            .collect::<Vec<_>>()
+    );
+}
+
+#[tokio::test]
+async fn rollup_results_match_raw_rows() {
+    let dir = scratch_dir("rollups");
+    let url = format!(
+        "sqlite://{}?mode=rwc",
+        dir.join("msg_history.sqlite").display()
+    );
+    let config = ConfigSnapshot {
+        timezone: TimeZoneSetting::parse("+08:00").unwrap(),
+        ..ConfigSnapshot::default()
+    };
+    let logger = open_with(&url, config).await;
+
+    for record in batch(1, 0..3) {
+        logger.log_record(record).await.unwrap();
+    }
+    logger.flush().await;
+    // 重建完成后预聚合表立即可用
+    logger.rebuild_keywords(|_| {}).await.unwrap();
+    // 之后的写入由写入任务增量维护预聚合表
+    for record in batch(100, 2..5) {
+        logger.log_record(record).await.unwrap();
+    }
+    logger.flush().await;
+
+    let raw = Database::connect(&url).await.unwrap();
+    for table in ["rollup_hourly", "rollup_user_daily", "rollup_word_daily"] {
+        assert!(count(&raw, table).await > 0, "{table} is empty");
+    }
+
+    let api = logger.query();
+    let date = |day| NaiveDate::from_ymd_opt(2024, 5, day).unwrap();
+    assert_same(
+        api,
+        MessageFilter::new()
+            .group(GROUP)
+            .date_range(date(1), date(5)),
+    )
+    .await;
+    assert_same(
+        api,
+        MessageFilter::new()
+            .group(GROUP)
+            .date_range(date(2), date(3)),
+    )
+    .await;
+    // 首尾不足一天的部分读原始表
+    assert_same(
+        api,
+        MessageFilter::new()
+            .group(GROUP)
+            .time_range(MAY1 + 10 * HOUR, MAY1 + 3 * DAY + 12 * HOUR),
+    )
+    .await;
+
+    drop(logger);
+    drop(raw);
+    std::fs::remove_dir_all(dir).unwrap();
+}
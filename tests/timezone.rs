@@ -0,0 +1,101 @@
+//! 时区配置的解析，以及更换时区后重算已存储消息的时间列
+
+mod common;
+
+use common::{GROUP, message, open_with, scratch_dir, time_columns, wait_for_meta};
+use kovi_plugin_msg_logger::config::{ConfigSnapshot, TimeZoneSetting};
+use kovi_plugin_msg_logger::db::MessageFilter;
+use sea_orm::Database;
+/// 2024-05-01 10:00 (+08:00)，即 02:00 UTC
+const DAY1: i64 = 1_714_528_800;
+const HOUR: i64 = 3600;
+
+fn config(timezone: &str) -> ConfigSnapshot {
+    ConfigSnapshot {
+        timezone: TimeZoneSetting::parse(timezone).unwrap(),
+        ..ConfigSnapshot::default()
+    }
+}
+
+#[tokio::test]
+async fn changing_timezone_recomputes_time_columns() {
+    let dir = scratch_dir("timezone");
+    let url = format!(
+        "sqlite://{}?mode=rwc",
+        dir.join("msg_history.sqlite").display()
+    );
+    let raw = Database::connect(&url).await.unwrap();
+
+    let logger = open_with(&url, config("+08:00")).await;
+    logger
+        .log_record(message(1, 7, "alice", "早上好", DAY1))
+        .await
+        .unwrap();
+    // +08:00 下已是次日零点
+    logger
+        .log_record(message(2, 7, "alice", "晚安", DAY1 + 14 * HOUR))
+        .await
+        .unwrap();
+    logger.flush().await;
+    wait_for_meta(&raw, "timezone", "+08:00").await;
+    assert_eq!(
+        time_columns(&raw).await,
+        vec![
+            (10, "2024-05-01".to_string()),
+            (0, "2024-05-02".to_string())
+        ]
+    );
+    drop(logger);
+
+    let logger = open_with(&url, config("+00:00")).await;
+    wait_for_meta(&raw, "timezone", "+00:00").await;
+    assert_eq!(
+        time_columns(&raw).await,
+        vec![
+            (2, "2024-05-01".to_string()),
+            (16, "2024-05-01".to_string())
+        ]
+    );
+
+    let group = MessageFilter::new().group(GROUP);
+    let api = logger.query();
+    let heatmap = api.hourly_heatmap_filtered(&group).await.unwrap();
+    let busy = heatmap
+        .iter()
+        .filter(|h| h.count > 0)
+        .map(|h| h.hour)
+        .collect::<Vec<_>>();
+    assert_eq!(busy, vec![2, 16]);
+    let trend = api.daily_trend_filtered(&group).await.unwrap();
+    assert_eq!(
+        trend
+            .iter()
+            .map(|d| (d.date.as_str(), d.count))
+            .collect::<Vec<_>>(),
+        vec![("2024-05-01", 2)]
+    );
+
+    drop(logger);
+    drop(raw);
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn malformed_offsets_are_rejected() {
+    for (input, canonical) in [
+        ("+08:00", "+08:00"),
+        ("-0530", "-05:30"),
+        ("+8", "+08:00"),
+        ("Asia/Shanghai", "Asia/Shanghai"),
+    ] {
+        assert_eq!(
+            TimeZoneSetting::parse(input).unwrap().canonical(),
+            canonical
+        );
+    }
+    for input in [
+        "+中a", "+中文", "+-5", "+08:60", "+0875", "+24", "+08:", "+:30", "+", "08:00",
+    ] {
+        assert!(TimeZoneSetting::parse(input).is_none(), "{input}");
+    }
+}
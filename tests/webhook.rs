@@ -0,0 +1,189 @@
+//! Webhook 规则评估与投递，使用本地 TCP 服务模拟接收端
+#![cfg(feature = "webhook")]
+
+mod common;
+
+use common::{GROUP, MEMORY, open, say};
+use kovi_plugin_msg_logger::config::WebhookConfig;
+use kovi_plugin_msg_logger::webhook;
+use serde_json::Value;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+use tokio::sync::{Mutex, mpsc};
+
+/// 按顺序返回预设状态码（用尽后返回 200），并转发收到的请求体
+async fn stand_in(statuses: Vec<u16>) -> (String, mpsc::UnboundedReceiver<Value>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/hook", listener.local_addr().unwrap());
+    let statuses = Arc::new(Mutex::new(statuses.into_iter()));
+    let (tx, rx) = mpsc::unbounded_channel();
+
+    tokio::spawn(async move {
+        loop {
+            let (stream, _) = listener.accept().await.unwrap();
+            let statuses = statuses.clone();
+            let tx = tx.clone();
+            tokio::spawn(async move {
+                let mut reader = BufReader::new(stream);
+                let mut content_length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).await.unwrap();
+                    let line = line.trim_end();
+                    if line.is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = line.split_once(':')
+                        && name.eq_ignore_ascii_case("content-length")
+                    {
+                        content_length = value.trim().parse().unwrap();
+                    }
+                }
+                let mut body = vec![0; content_length];
+                reader.read_exact(&mut body).await.unwrap();
+
+                let status = statuses.lock().await.next().unwrap_or(200);
+                if status == 200 {
+                    tx.send(serde_json::from_slice(&body).unwrap()).ok();
+                }
+                let response = format!(
+                    "HTTP/1.1 {status} X\r\ncontent-length: 0\r\nconnection: close\r\n\r\n"
+                );
+                reader
+                    .into_inner()
+                    .write_all(response.as_bytes())
+                    .await
+                    .ok();
+            });
+        }
+    });
+    (url, rx)
+}
+
+fn config(url: String, rules: &str) -> WebhookConfig {
+    let mut config: WebhookConfig = kovi::toml::from_str(rules).unwrap();
+    config.enabled = true;
+    config.url = url;
+    config
+}
+
+async fn next(rx: &mut mpsc::UnboundedReceiver<Value>) -> Value {
+    tokio::time::timeout(Duration::from_secs(10), rx.recv())
+        .await
+        .expect("webhook not delivered")
+        .unwrap()
+}
+
+async fn assert_silent(rx: &mut mpsc::UnboundedReceiver<Value>) {
+    let extra = tokio::time::timeout(Duration::from_millis(500), rx.recv()).await;
+    assert!(extra.is_err(), "unexpected webhook: {:?}", extra);
+}
+
+#[tokio::test]
+async fn keyword_and_user_rules_deliver_with_retry() {
+    // 首个请求返回 500，验证重试
+    let (url, mut rx) = stand_in(vec![500]).await;
+    let logger = open(MEMORY).await;
+    let rules = r#"
+        [[rules]]
+        name = "report"
+        type = "keyword"
+        keywords = ["举报", "SPAM"]
+        groups = [10001]
+
+        [[rules]]
+        name = "vip"
+        type = "user"
+        user_ids = [42]
+    "#;
+    webhook::spawn(&logger, config(url, rules)).unwrap();
+
+    say(&logger, 1, 7, GROUP, "今天天气不错").await;
+    say(&logger, 2, 7, 20002, "我要举报").await;
+    say(&logger, 3, 7, GROUP, "this is spam!").await;
+    logger.flush().await;
+
+    let alert = next(&mut rx).await;
+    assert_eq!(alert["rule"], "report");
+    assert_eq!(alert["type"], "keyword");
+    assert_eq!(alert["detail"], "spam");
+    assert_eq!(alert["group_id"], GROUP);
+    assert_eq!(alert["message"]["message_id"], 3);
+    assert_silent(&mut rx).await;
+
+    say(&logger, 4, 42, 20002, "大家好").await;
+    logger.flush().await;
+    let alert = next(&mut rx).await;
+    assert_eq!(alert["rule"], "vip");
+    assert_eq!(alert["user_id"], 42);
+}
+
+#[tokio::test]
+async fn rate_rule_fires_once_per_cooldown() {
+    let (url, mut rx) = stand_in(Vec::new()).await;
+    let logger = open(MEMORY).await;
+    let rules = r#"
+        cooldown_secs = 3600
+
+        [[rules]]
+        name = "flood"
+        type = "rate"
+        threshold = 5
+        window_secs = 60
+    "#;
+    webhook::spawn(&logger, config(url, rules)).unwrap();
+
+    for id in 1..=4 {
+        say(&logger, id, id, GROUP, "刷屏").await;
+    }
+    logger.flush().await;
+    assert_silent(&mut rx).await;
+
+    for id in 5..=12 {
+        say(&logger, id, id, GROUP, "刷屏").await;
+    }
+    logger.flush().await;
+    let alert = next(&mut rx).await;
+    assert_eq!(alert["type"], "rate");
+    assert_eq!(alert["detail"], "5 messages in 60s");
+    assert_silent(&mut rx).await;
+}
+
+#[tokio::test]
+async fn regex_rule_respects_rate_limit() {
+    let (url, mut rx) = stand_in(Vec::new()).await;
+    let logger = open(MEMORY).await;
+    let rules = r#"
+        cooldown_secs = 0
+        max_per_minute = 2
+
+        [[rules]]
+        name = "order"
+        type = "regex"
+        pattern = '#\d{4}'
+    "#;
+    webhook::spawn(&logger, config(url, rules)).unwrap();
+
+    for id in 1..=4 {
+        say(&logger, id, 7, GROUP, &format!("订单 #{}", 1000 + id)).await;
+    }
+    logger.flush().await;
+
+    assert_eq!(next(&mut rx).await["detail"], "#1001");
+    assert_eq!(next(&mut rx).await["detail"], "#1002");
+    assert_silent(&mut rx).await;
+}
+
+#[test]
+fn invalid_rules_are_rejected() {
+    let rules = r#"
+        [[rules]]
+        name = "broken"
+        type = "regex"
+        pattern = "("
+    "#;
+    let config: WebhookConfig = kovi::toml::from_str(rules).unwrap();
+    assert!(webhook::RuleEngine::new(&config.rules, Duration::ZERO).is_err());
+}
@@ -0,0 +1,109 @@
+//! 批量写入路径：用户行与消息的写入顺序、计数
+
+mod common;
+
+use common::{GROUP, NOW, db_url, open};
+use kovi_plugin_msg_logger::db::{IncomingMessage, MessageFilter};
+use kovi_plugin_msg_logger::entities::prelude::Users;
+use sea_orm::{Database, EntityTrait};
+
+fn message(message_id: i64, user_id: i64) -> IncomingMessage {
+    common::message(
+        message_id,
+        user_id,
+        "newbie",
+        "第一次发言",
+        NOW + message_id,
+    )
+}
+
+#[tokio::test]
+async fn first_message_from_new_user_is_written() {
+    let logger = open(&db_url("write-first")).await;
+    logger.log_record(message(1, 4242)).await.unwrap();
+    logger.flush().await;
+
+    let filter = MessageFilter::new().group(GROUP);
+    assert_eq!(
+        logger
+            .query()
+            .message_count_filtered(&filter)
+            .await
+            .unwrap(),
+        1
+    );
+    let user = logger.query().user_stats(4242, Some(GROUP)).await.unwrap();
+    assert_eq!(user.nickname, "newbie");
+    assert_eq!(user.total_messages, 1);
+
+    let metrics = logger.metrics().await;
+    assert_eq!(metrics.messages_written, 1);
+    assert_eq!(metrics.messages_dropped, 0);
+}
+
+#[tokio::test]
+async fn duplicate_message_does_not_bump_user_count() {
+    let url = db_url("write-duplicate");
+    let logger = open(&url).await;
+    logger.log_record(message(1, 4242)).await.unwrap();
+    logger.log_record(message(1, 4242)).await.unwrap();
+    logger.log_record(message(2, 4242)).await.unwrap();
+    logger.flush().await;
+
+    let db = Database::connect(&url).await.unwrap();
+    let users = Users::find().all(&db).await.unwrap();
+    assert_eq!(users.len(), 1);
+    assert_eq!(users[0].message_count, 2);
+}
+
+#[tokio::test]
+async fn replayed_messages_across_flushes_are_written_once() {
+    let url = db_url("write-replay");
+    let logger = open(&url).await;
+    let private = IncomingMessage {
+        group_id: None,
+        peer_id: Some(4242),
+        ..message(3, 4242)
+    };
+    logger.log_record(message(1, 4242)).await.unwrap();
+    logger.log_record(private.clone()).await.unwrap();
+    logger.flush().await;
+    // 断线重连后重放的同一批消息
+    logger.log_record(message(1, 4242)).await.unwrap();
+    logger.log_record(private).await.unwrap();
+    logger.log_record(message(2, 4242)).await.unwrap();
+    logger.flush().await;
+
+    let filter = MessageFilter::new().group(GROUP);
+    let api = logger.query();
+    assert_eq!(api.message_count_filtered(&filter).await.unwrap(), 2);
+    assert_eq!(api.private_conversation(4242, 10).await.unwrap().len(), 1);
+    let words = api.word_cloud_filtered(&filter, 10).await.unwrap();
+    assert!(!words.is_empty());
+    assert!(words.iter().all(|w| w.count == 2), "{words:?}");
+
+    let db = Database::connect(&url).await.unwrap();
+    let users = Users::find().all(&db).await.unwrap();
+    assert_eq!(users[0].message_count, 3);
+}
+
+#[tokio::test]
+async fn unknown_message_ids_are_not_deduplicated() {
+    let logger = open(&db_url("write-unknown-id")).await;
+    for (user_id, text) in [(7, "第一条"), (8, "第二条"), (7, "第三条")] {
+        // 0 表示来源未提供消息 ID
+        let record = common::message(0, user_id, "n", text, NOW);
+        logger.log_record(record).await.unwrap();
+    }
+    logger.flush().await;
+
+    let filter = MessageFilter::new().group(GROUP);
+    assert_eq!(
+        logger
+            .query()
+            .message_count_filtered(&filter)
+            .await
+            .unwrap(),
+        3
+    );
+}